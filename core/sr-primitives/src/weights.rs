@@ -22,10 +22,18 @@
 //!
 //! Note that the decl_module macro _cannot_ enforce this and will simply fail if an invalid struct
 //! (something that does not  implement `Weighable`) is passed in.
+//!
+//! The weight of a transaction is only half the story of its final fee: a runtime also needs a
+//! [`WeightToFee`] implementation to turn that abstract `Weight` into an actual chargeable
+//! balance, after the `FeeMultiplier` has been applied to account for block congestion. The
+//! encoded length of the transaction is priced independently via [`LengthToFee`], so the final
+//! charge is `length_to_fee(len) + weight_to_fee(weight)`.
 
 use crate::codec::{Decode, Encode};
 use crate::Perbill;
-use crate::traits::Zero;
+use crate::traits::{Zero, SimpleArithmetic};
+use rstd::marker::PhantomData;
+use rstd::prelude::*;
 
 /// The final type that each `#[weight = $x:expr]`'s
 /// expression must evaluate to.
@@ -35,6 +43,34 @@ pub type Weight = u32;
 pub const MAX_TRANSACTIONS_WEIGHT: u32 = 4 * 1024 * 1024;
 /// Target block saturation: 25% of max block saturation = 1mb
 pub const IDEAL_TRANSACTIONS_WEIGHT: u32 = 1024 * 1024;
+/// Portion of `MAX_TRANSACTIONS_WEIGHT` reserved exclusively for `Operational` dispatches, on
+/// top of whatever the `Normal` class has already consumed.
+pub const OPERATIONAL_TRANSACTIONS_RESERVE: Perbill = Perbill::from_percent(25);
+
+/// A generalized group of dispatch types.
+///
+/// A transaction's class determines how it interacts with the fee market and block saturation:
+/// `Normal` dispatches are subject to both, `Operational` ones bypass the `FeeMultiplier` surge
+/// pricing and are limited to the reserved operational share of the block (not the `Normal`
+/// share), and
+/// `Mandatory` ones (e.g. inherents) are never refused and never counted towards the fee-bearing
+/// weight at all.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchClass {
+	/// A normal dispatch, subject to the fee market.
+	Normal,
+	/// An operational dispatch, e.g. governance or emergency calls.
+	Operational,
+	/// A mandatory dispatch, e.g. an inherent. Always included, never charged.
+	Mandatory,
+}
+
+impl Default for DispatchClass {
+	fn default() -> Self {
+		DispatchClass::Normal
+	}
+}
 
 /// A `Call` enum (aka transaction) that can be weighted using the custom weight attribute of
 /// its dispatchable functions. Is implemented by default in the `decl_module!`.
@@ -45,6 +81,77 @@ pub trait Weighable {
 	/// Return the weight of this call.
 	/// The `len` argument is the encoded length of the transaction/call.
 	fn weight(&self, len: usize) -> Weight;
+
+	/// Return the `DispatchClass` of this call. Defaults to `Normal`, so that only calls that
+	/// explicitly opt in (e.g. governance or emergency calls) can bypass the fee market.
+	fn classify(&self) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Tracks the weight consumed by each `DispatchClass` within a single block.
+///
+/// `Normal` dispatches are capped at `MAX_TRANSACTIONS_WEIGHT` minus the reserved operational
+/// share; `Operational` dispatches are tracked separately and capped at that reserved share
+/// alone, so they cannot use any unspent `Normal` share; `Mandatory` dispatches bypass this
+/// accounting entirely.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlockWeight {
+	normal: Weight,
+	operational: Weight,
+}
+
+impl BlockWeight {
+	/// Maximum weight reserved for `Operational` dispatches, on top of the normal ceiling.
+	pub fn operational_limit() -> Weight {
+		OPERATIONAL_TRANSACTIONS_RESERVE * MAX_TRANSACTIONS_WEIGHT
+	}
+
+	/// Maximum weight available to `Normal` dispatches.
+	pub fn normal_limit() -> Weight {
+		MAX_TRANSACTIONS_WEIGHT - Self::operational_limit()
+	}
+
+	/// Records `weight` against `class`. Returns `false`, leaving the accounting unchanged, if
+	/// doing so would exceed the limit for that class. `Mandatory` dispatches always succeed and
+	/// are never recorded, since they never count towards the fee-bearing weight.
+	pub fn add(&mut self, weight: Weight, class: DispatchClass) -> bool {
+		match class {
+			DispatchClass::Normal => {
+				let next = self.normal.saturating_add(weight);
+				if next > Self::normal_limit() {
+					return false;
+				}
+				self.normal = next;
+				true
+			},
+			DispatchClass::Operational => {
+				let next = self.operational.saturating_add(weight);
+				if next > Self::operational_limit() {
+					return false;
+				}
+				self.operational = next;
+				true
+			},
+			DispatchClass::Mandatory => true,
+		}
+	}
+
+	/// Weight consumed so far by the `Normal` class.
+	pub fn normal(&self) -> Weight {
+		self.normal
+	}
+
+	/// Weight consumed so far by the `Operational` class.
+	pub fn operational(&self) -> Weight {
+		self.operational
+	}
+
+	/// Total fee-bearing weight consumed so far (`Normal` plus `Operational`).
+	pub fn total(&self) -> Weight {
+		self.normal.saturating_add(self.operational)
+	}
 }
 
 /// Default type used as the weight representative in a `#[weight = x]` attribute.
@@ -52,9 +159,9 @@ pub trait Weighable {
 /// A user may pass in any other type that implements [`Weighable`]. If not, the `Default`
 /// implementation of [`TransactionWeight`] is used.
 pub enum TransactionWeight {
-	/// Basic weight (base, byte).
-	/// The values contained are the base weight and byte weight respectively.
-	Basic(Weight, Weight),
+	/// Basic, purely computational, weight. The encoded length of the transaction is priced
+	/// separately, via a runtime's [`LengthToFee`] implementation.
+	Basic(Weight),
 	/// Maximum fee. This implies that this transaction _might_ get included but
 	/// no more transaction can be added. This can be done by setting the
 	/// implementation to _maximum block weight_.
@@ -65,9 +172,9 @@ pub enum TransactionWeight {
 }
 
 impl Weighable for TransactionWeight {
-	fn weight(&self, len: usize) -> Weight {
+	fn weight(&self, _len: usize) -> Weight {
 		match self {
-			TransactionWeight::Basic(base, byte) => base + byte * len as Weight,
+			TransactionWeight::Basic(base) => *base,
 			TransactionWeight::Max => 3 * 1024 * 1024,
 			TransactionWeight::Free => 0,
 		}
@@ -76,84 +183,472 @@ impl Weighable for TransactionWeight {
 
 impl Default for TransactionWeight {
 	fn default() -> Self {
-		// This implies that the weight is currently equal to tx-size, nothing more
-		// for all substrate transactions that do NOT explicitly annotate weight.
+		// No computational weight by default; the length of the transaction is still priced via
+		// `LengthToFee`, so this does not imply the transaction is free.
 		// TODO #2431 needs to be updated with proper max values.
-		TransactionWeight::Basic(0, 1)
+		TransactionWeight::Basic(0)
+	}
+}
+
+/// A struct holding the value for one term of a [`WeightToFeePolynomial`].
+pub struct WeightToFeeCoefficient<Balance> {
+	/// The integral part of the coefficient.
+	pub coeff_integer: Balance,
+	/// The fractional part of the coefficient, as a `Perbill` added on top of `coeff_integer`.
+	pub coeff_frac: Perbill,
+	/// Whether this term should be subtracted (`true`) or added (`false`) to the final fee.
+	pub negative: bool,
+	/// The degree of `weight` that this coefficient applies to, e.g. `2` for a quadratic term.
+	pub degree: u8,
+}
+
+/// The set of coefficients that describe a [`WeightToFeePolynomial`], in no particular order.
+pub type WeightToFeeCoefficients<Balance> = Vec<WeightToFeeCoefficient<Balance>>;
+
+/// Handles converting a weight scalar into a chargeable fee balance.
+///
+/// This should typically create a mapping between the following ranges:
+///   - `[0, MAX_TRANSACTIONS_WEIGHT]`
+///   - `[Balance::min_value(), Balance::max_value()]`
+///
+/// A runtime can implement this trait directly for full control, or implement
+/// [`WeightToFeePolynomial`] instead to get a sane implementation for free.
+pub trait WeightToFee {
+	/// The type that is returned as the final fee.
+	type Balance: SimpleArithmetic + From<u32> + Copy;
+
+	/// Calculates the fee from the given `weight`.
+	fn weight_to_fee(weight: &Weight) -> Self::Balance;
+}
+
+/// A `WeightToFee` implementation described as a polynomial in `weight`, i.e.
+/// `fee = sum_i coeff_i * weight^degree_i`, evaluated with saturating arithmetic throughout.
+///
+/// This allows a runtime to express non-linear (e.g. super-linear) fee curves by combining a
+/// handful of hand-picked [`WeightToFeeCoefficient`]s, rather than a single linear factor.
+pub trait WeightToFeePolynomial {
+	/// The type that is returned as the final fee.
+	type Balance: SimpleArithmetic + From<u32> + Copy;
+
+	/// Returns the polynomial that describes the relation between weight and fee.
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance>;
+}
+
+/// Evaluates `coefficients` at `x`, i.e. `sum_i coeff_i * x^degree_i`, saturating throughout.
+fn evaluate_polynomial<Balance: SimpleArithmetic + From<u32> + Copy>(
+	coefficients: &[WeightToFeeCoefficient<Balance>],
+	x: u32,
+) -> Balance {
+	coefficients.iter().fold(Balance::from(0u32), |acc, term| {
+		let mut x_pow = Balance::from(1u32);
+		for _ in 0..term.degree {
+			x_pow = x_pow.saturating_mul(Balance::from(x));
+		}
+
+		let frac = term.coeff_frac * x_pow;
+		let integer = term.coeff_integer.saturating_mul(x_pow);
+
+		if term.negative {
+			acc.saturating_sub(frac).saturating_sub(integer)
+		} else {
+			acc.saturating_add(frac).saturating_add(integer)
+		}
+	})
+}
+
+impl<T: WeightToFeePolynomial> WeightToFee for T {
+	type Balance = <T as WeightToFeePolynomial>::Balance;
+
+	fn weight_to_fee(weight: &Weight) -> Self::Balance {
+		evaluate_polynomial(&T::polynomial(), *weight)
+	}
+}
+
+/// Computes the weight portion of a transaction's fee: `weight_to_fee` of the weight, after
+/// `multiplier` has been applied (exempting `class`es that bypass the fee market, per
+/// [`FeeMultiplier::apply_to_class`]). This is the single pluggable conversion a runtime applies
+/// uniformly across all pallets.
+pub fn compute_fee<T: WeightToFee>(weight: Weight, multiplier: FeeMultiplier, class: DispatchClass) -> T::Balance {
+	T::weight_to_fee(&multiplier.apply_to_class(weight, class))
+}
+
+/// Handles converting the encoded length of a transaction into a fee, independent of its
+/// computational [`Weight`]. The final charge for a transaction is
+/// `length_to_fee(len) + weight_to_fee(weight)`, with only the weight portion subject to the
+/// `FeeMultiplier`. This lets a runtime price bytes linearly while pricing CPU super-linearly
+/// (or vice versa), which a single shared scale cannot express.
+pub trait LengthToFee {
+	/// The type that is returned as the final fee.
+	type Balance: SimpleArithmetic + From<u32> + Copy;
+
+	/// Calculates the fee from the encoded length `len`.
+	fn length_to_fee(len: u32) -> Self::Balance;
+}
+
+/// Any [`WeightToFeePolynomial`] can also be used to price length, by evaluating the same
+/// polynomial with `len` in place of `weight`.
+impl<T: WeightToFeePolynomial> LengthToFee for T {
+	type Balance = <T as WeightToFeePolynomial>::Balance;
+
+	fn length_to_fee(len: u32) -> Self::Balance {
+		evaluate_polynomial(&T::polynomial(), len)
+	}
+}
+
+/// Computes a transaction's total fee: `length_to_fee(len) + weight_to_fee(weight)`, with only
+/// the weight portion subject to `multiplier` (and `class`), per [`compute_fee`]. `Mandatory`
+/// dispatches are charged nothing, not even for their length.
+pub fn compute_transaction_fee<T>(len: u32, weight: Weight, multiplier: FeeMultiplier, class: DispatchClass) -> T::Balance
+where
+	T: WeightToFee + LengthToFee<Balance = <T as WeightToFee>::Balance>,
+{
+	if class == DispatchClass::Mandatory {
+		return Zero::zero();
 	}
+	T::length_to_fee(len).saturating_add(compute_fee::<T>(weight, multiplier, class))
+}
+
+/// A `WeightToFee` implementation that simply maps one unit of weight to one unit of fee.
+pub struct IdentityFee<T>(PhantomData<T>);
+
+impl<T: SimpleArithmetic + From<u32> + Copy> WeightToFeePolynomial for IdentityFee<T> {
+	type Balance = T;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		vec![WeightToFeeCoefficient {
+			coeff_integer: 1u32.into(),
+			coeff_frac: Perbill::zero(),
+			negative: false,
+			degree: 1,
+		}]
+	}
+}
+
+/// Computes `a * b / c`, widening the intermediate product through a 256-bit accumulator so
+/// that the result is only reported as overflowing when it genuinely doesn't fit in an `i128`,
+/// rather than whenever the unscaled product of `a` and `b` happens to.
+fn multiply_by_rational(a: i128, b: i128, c: i128) -> Option<i128> {
+	if c == 0 {
+		return None;
+	}
+	let result_negative = ((a < 0) != (b < 0)) != (c < 0);
+	let (product_low, product_high) = full_mul_u128(abs_u128(a), abs_u128(b));
+	let quotient = div_u256_by_u128(product_high, product_low, abs_u128(c))?;
+
+	if result_negative {
+		if quotient > abs_u128(i128::min_value()) {
+			None
+		} else if quotient == abs_u128(i128::min_value()) {
+			Some(i128::min_value())
+		} else {
+			Some(-(quotient as i128))
+		}
+	} else if quotient > i128::max_value() as u128 {
+		None
+	} else {
+		Some(quotient as i128)
+	}
+}
+
+/// The absolute value of `x`, represented as a `u128` (so it doesn't overflow for `i128::MIN`).
+fn abs_u128(x: i128) -> u128 {
+	if x == i128::min_value() {
+		i128::max_value() as u128 + 1
+	} else {
+		x.abs() as u128
+	}
+}
+
+/// Computes the full, unsigned, `256`-bit product of `a` and `b` as a `(low, high)` pair of
+/// `u128` limbs, using the schoolbook method over `64`-bit halves so no partial product
+/// overflows a `u128`.
+fn full_mul_u128(a: u128, b: u128) -> (u128, u128) {
+	let (a_lo, a_hi) = (a as u64 as u128, a >> 64);
+	let (b_lo, b_hi) = (b as u64 as u128, b >> 64);
+
+	let lo_lo = a_lo * b_lo;
+	let hi_lo = a_hi * b_lo;
+	let lo_hi = a_lo * b_hi;
+	let hi_hi = a_hi * b_hi;
+
+	let cross = (lo_lo >> 64) + (hi_lo & u64::max_value() as u128) + (lo_hi & u64::max_value() as u128);
+	let low = (lo_lo & u64::max_value() as u128) | (cross << 64);
+	let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+	(low, high)
 }
 
-/// A wrapper for fee multiplier.
-/// This is to simulate a `Perbill` in the range [-1, infinity]
+/// Divides the `256`-bit unsigned value `(high, low)` (`high` being the more significant limb)
+/// by `divisor`, returning `None` if `divisor` is zero or the quotient doesn't fit in a `u128`.
 ///
-/// The fee multiplier is always multiplied by the weight (as denoted by `TransactionWeight` on a
-/// per-transaction basis with `#[weight]` annotation) of the transaction to obtain the final fee.
+/// This is a plain bit-by-bit restoring long division; `high`/`low` are rarely more than a
+/// handful of bits wide in practice (they come from multiplying two `Fixed128`s), so the fixed
+/// `256` iterations are cheap relative to the alternative of depending on a `u256` crate.
+fn div_u256_by_u128(high: u128, low: u128, divisor: u128) -> Option<u128> {
+	if divisor == 0 {
+		return None;
+	}
+	if high == 0 {
+		return Some(low / divisor);
+	}
+
+	let mut remainder = 0u128;
+	let mut quotient = 0u128;
+	for i in (0..256).rev() {
+		let bit = if i >= 128 { (high >> (i - 128)) & 1 } else { (low >> i) & 1 };
+		let carry = remainder >> 127;
+		remainder = (remainder << 1) | bit;
+
+		let exceeds = carry == 1 || remainder >= divisor;
+		if exceeds {
+			remainder = remainder.wrapping_sub(divisor);
+		}
+
+		if i < 128 {
+			quotient |= (exceeds as u128) << i;
+		} else if exceeds {
+			// A quotient bit at position >= 128 means the result can't fit in a `u128`.
+			return None;
+		}
+	}
+	Some(quotient)
+}
+
+/// A signed fixed point number, internally represented as an `i128`, with 18 fractional digits.
 ///
-/// One can define how this conversion evolves based on the previous block weight by implementing
-/// the `FeeMultiplierUpdate` type of the `system` trait.
-#[cfg_attr(feature = "std", derive(PartialEq, Eq, Debug))]
-#[derive(Encode, Decode, Clone, Copy)]
-pub enum FeeMultiplier {
-	/// Should be interpreted as a positive ratio added to the weight, i.e. `weight + weight * p`
-	/// where `p` is a small `Perbill`.
+/// This has enough range to comfortably represent the fee multiplier (whose useful domain is
+/// roughly `[0, 100_000]`, since it multiplies a weight directly rather than being added as a
+/// delta) without the precision loss that a `Perbill`-based approximation incurs over many blocks
+/// of accumulation.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed128(i128);
+
+impl Fixed128 {
+	/// The accuracy of this type, i.e. the number that `1.0` is represented as internally.
+	const DIV: i128 = 1_000_000_000_000_000_000;
+
+	/// Creates `self` from a raw, already-scaled, `i128` value.
+	pub const fn from_parts(parts: i128) -> Self {
+		Self(parts)
+	}
+
+	/// Creates `self` representing the natural number `int`.
+	pub fn from_natural(int: i128) -> Self {
+		Self(int.saturating_mul(Self::DIV))
+	}
+
+	/// Creates `self` from a rational number `n / d`, saturating both the intermediate
+	/// conversion and the final result.
+	pub fn saturating_from_rational(n: i128, d: i128) -> Self {
+		if d == 0 {
+			return Self(0);
+		}
+		Self(multiply_by_rational(n, Self::DIV, d).unwrap_or(if (n < 0) != (d < 0) {
+			i128::min_value()
+		} else {
+			i128::max_value()
+		}))
+	}
+
+	/// The raw, scaled, inner value.
+	pub fn deconstruct(self) -> i128 {
+		self.0
+	}
+
+	/// Checked addition.
+	pub fn checked_add(self, rhs: Self) -> Option<Self> {
+		self.0.checked_add(rhs.0).map(Self)
+	}
+
+	/// Saturating addition.
+	pub fn saturating_add(self, rhs: Self) -> Self {
+		Self(self.0.saturating_add(rhs.0))
+	}
+
+	/// Checked subtraction.
+	pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+		self.0.checked_sub(rhs.0).map(Self)
+	}
+
+	/// Saturating subtraction.
+	pub fn saturating_sub(self, rhs: Self) -> Self {
+		Self(self.0.saturating_sub(rhs.0))
+	}
+
+	/// Checked multiplication.
+	///
+	/// Unlike a naive `self.0 * rhs.0 / DIV`, the intermediate product is computed in a widened
+	/// 256-bit accumulator, so this only reports overflow when the mathematical result itself
+	/// doesn't fit in an `i128`, not whenever the two (already `DIV`-scaled) raw values happen to
+	/// overflow `i128` on their own.
+	pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+		multiply_by_rational(self.0, rhs.0, Self::DIV).map(Self)
+	}
+
+	/// Saturating multiplication.
+	pub fn saturating_mul(self, rhs: Self) -> Self {
+		self.checked_mul(rhs).unwrap_or_else(|| {
+			if (self.0 < 0) != (rhs.0 < 0) {
+				Self(i128::min_value())
+			} else {
+				Self(i128::max_value())
+			}
+		})
+	}
+
+	/// Checked division.
 	///
-	Positive(Perbill, Weight),
-	/// Should be interpreted as a negative ratio subtracted from the weight, i.e.
-	/// `weight - weight * p` where `p` is a small `Perbill`.
-	Negative(Perbill),
+	/// Uses the same widened intermediate as [`checked_mul`](Self::checked_mul), for the same
+	/// reason.
+	pub fn checked_div(self, rhs: Self) -> Option<Self> {
+		if rhs.0 == 0 {
+			return None;
+		}
+		multiply_by_rational(self.0, Self::DIV, rhs.0).map(Self)
+	}
+
+	/// Saturating division.
+	pub fn saturating_div(self, rhs: Self) -> Self {
+		self.checked_div(rhs).unwrap_or_else(|| {
+			if (self.0 < 0) != (rhs.0 < 0) {
+				Self(i128::min_value())
+			} else {
+				Self(i128::max_value())
+			}
+		})
+	}
+
+	/// Checked multiplication of `self` into an integer `Weight`.
+	pub fn checked_mul_int(self, n: Weight) -> Option<Weight> {
+		let r = multiply_by_rational(self.0, n as i128, Self::DIV)?;
+		if r < 0 || r > Weight::max_value() as i128 {
+			None
+		} else {
+			Some(r as Weight)
+		}
+	}
+
+	/// Multiplies `self` into an integer `Weight`, saturating the result.
+	pub fn saturating_mul_int(self, n: Weight) -> Weight {
+		self.checked_mul_int(n).unwrap_or_else(|| {
+			if self.0 < 0 { 0 } else { Weight::max_value() }
+		})
+	}
 }
 
+impl Default for Fixed128 {
+	fn default() -> Self {
+		Self(0)
+	}
+}
+
+/// A multiplier applied to the weight of a transaction to obtain its final fee.
+///
+/// Internally represented as a [`Fixed128`] factor (default `1.0`, meaning "unchanged"), so that
+/// `apply_to` is a single `mul_int` and composing two multipliers with `sum` is ordinary
+/// addition, rather than the ad-hoc `Perbill`-pair carry logic this type used to require.
+///
+/// One can define how this factor evolves based on the previous block weight by implementing the
+/// `FeeMultiplierUpdate` type of the `system` trait, see [`TargetedFeeAdjustment`].
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FeeMultiplier(Fixed128);
+
 impl FeeMultiplier {
+	/// Wraps a raw [`Fixed128`] factor as a `FeeMultiplier`.
+	pub const fn from_fixed128(multiplier: Fixed128) -> Self {
+		Self(multiplier)
+	}
+
+	/// Unwraps the underlying [`Fixed128`] factor.
+	pub fn into_fixed128(self) -> Fixed128 {
+		self.0
+	}
+
 	/// Applies the self, as a multiplier, to the given weight.
 	pub fn apply_to(&self, weight: Weight) -> Weight {
-		match *self {
-			FeeMultiplier::Positive(p, r) => weight + weight.saturating_mul(r).saturating_add(p * weight),
-			FeeMultiplier::Negative(p) => weight.checked_sub(p * weight).unwrap_or(Zero::zero()),
+		self.0.saturating_mul_int(weight)
+	}
+
+	/// Applies `self` to `weight`, unless `class` should bypass the fee market entirely.
+	///
+	/// `Operational` dispatches are exempt from surge pricing (they pay the base rate, i.e.
+	/// `weight` unchanged), and `Mandatory` dispatches are free.
+	pub fn apply_to_class(&self, weight: Weight, class: DispatchClass) -> Weight {
+		match class {
+			DispatchClass::Normal => self.apply_to(weight),
+			DispatchClass::Operational => weight,
+			DispatchClass::Mandatory => Zero::zero(),
 		}
 	}
 
-	/// consumes self and returns the combination of `self` and `rhs`, taking the sign into account.
+	/// Consumes self and returns the combination of `self` and `rhs`.
 	pub fn sum(self, rhs: Self) -> Self {
-		match (self, rhs) {
-			(FeeMultiplier::Positive(p1, r1), FeeMultiplier::Positive(p2, r2)) => {
-				// because the add implementation silently saturates. A perbill should saturate but
-				// once we have a proper `Float` type this can be improved.
-				let billion = 1_000_000_000;
-				if p1.0 + p2.0 > billion {
-					FeeMultiplier::Positive(
-						Perbill::from_parts((p1.0 + p2.0) % billion),
-						r1.saturating_add(r2).saturating_add(1)
-					)
-				} else {
-					FeeMultiplier::Positive(p1 + p2, r1.saturating_add(r2))
-				}
-			},
-			(FeeMultiplier::Negative(p1), FeeMultiplier::Negative(p2)) => {
-				// the sum impl of perbill simply caps this. This cannot grow more than -1.
-				FeeMultiplier::Negative(p1 + p2)
-			},
-			(FeeMultiplier::Positive(p1, r1), FeeMultiplier::Negative(p2)) => {
-				if let Some(new_p) = p1.0.checked_sub(p2.0) {
-					FeeMultiplier::Positive(Perbill::from_parts(new_p), r1)
-				} else {
-					let new_p = Perbill::from_parts(p2.0 - p1.0);
-					if r1 > 0 {
-						FeeMultiplier::Positive(new_p, r1-1)
-					} else {
-						FeeMultiplier::Negative(new_p)
-					}
-				}
-			},
-			(FeeMultiplier::Negative(_), FeeMultiplier::Positive(_, _)) => {
-				rhs.sum(self)
-			},
-		}
+		Self(self.0.saturating_add(rhs.0))
 	}
 }
 
 impl Default for FeeMultiplier {
 	fn default() -> Self {
-		FeeMultiplier::Positive(Perbill::zero(), Zero::zero())
+		// No change to the weight, i.e. a factor of `1.0`.
+		FeeMultiplier(Fixed128::from_natural(1))
+	}
+}
+
+/// The static parameters that configure a [`TargetedFeeAdjustment`].
+pub trait TargetedFeeAdjustmentBounds {
+	/// The target fullness (`s*`) of the `Normal` dispatch class, e.g. 25%.
+	const TARGET: Perbill;
+	/// `v`: how aggressively the multiplier reacts to the block being over/under `TARGET`.
+	const VARIABILITY: Fixed128;
+	/// Hard floor for the multiplier. This must be set above zero: once the multiplier
+	/// saturates to zero it could never recover, since `next = prev * (...)` and `0 * x == 0`.
+	const MIN_MULTIPLIER: FeeMultiplier;
+	/// Hard ceiling for the multiplier.
+	const MAX_MULTIPLIER: FeeMultiplier;
+}
+
+/// The [`TargetedFeeAdjustmentBounds`] a runtime gets if it doesn't need anything custom: targets
+/// the existing [`IDEAL_TRANSACTIONS_WEIGHT`] fullness of [`MAX_TRANSACTIONS_WEIGHT`] (`25%`, which
+/// falls out of those two constants for free), with a `10%` reaction variability and multiplier
+/// bounds wide enough to swing from near-zero to a hundred-thousandfold surge.
+pub struct DefaultFeeAdjustment;
+
+impl TargetedFeeAdjustmentBounds for DefaultFeeAdjustment {
+	const TARGET: Perbill = Perbill::from_percent(IDEAL_TRANSACTIONS_WEIGHT * 100 / MAX_TRANSACTIONS_WEIGHT);
+	const VARIABILITY: Fixed128 = Fixed128::from_parts(100_000_000_000_000_000); // 0.1
+	const MIN_MULTIPLIER: FeeMultiplier = FeeMultiplier::from_fixed128(Fixed128::from_parts(1_000_000_000_000)); // ~0
+	const MAX_MULTIPLIER: FeeMultiplier = FeeMultiplier::from_fixed128(Fixed128::from_parts(100_000_000_000_000_000_000_000));
+}
+
+/// A `FeeMultiplier` update function that targets a fixed fullness `s*` (`T::TARGET`) for the
+/// `Normal` dispatch class, recomputed from the previous block's weight every block.
+///
+/// Given the current normal-class fullness `s = normal_weight / max_normal_weight`, computes
+/// `next = prev * (1 + v * (s - s*) + (v * (s - s*))^2 / 2)`, then clamps the result into
+/// `[T::MIN_MULTIPLIER, T::MAX_MULTIPLIER]`. This is monotonic in `s` and leaves the multiplier
+/// unchanged when `s == s*`.
+///
+/// More information can be found at:
+/// <https://research.web3.foundation/en/latest/polkadot/Token%20Economics/#relay-chain-transaction-fees>
+pub struct TargetedFeeAdjustment<T>(PhantomData<T>);
+
+impl<T: TargetedFeeAdjustmentBounds> TargetedFeeAdjustment<T> {
+	/// Computes the next fee multiplier from the `previous` one, given how full the block's
+	/// `Normal` dispatch class was (`normal_weight` out of `max_normal_weight`).
+	pub fn convert(previous: FeeMultiplier, normal_weight: Weight, max_normal_weight: Weight) -> FeeMultiplier {
+		let max_normal_weight = max_normal_weight.max(1);
+
+		let target = Fixed128::saturating_from_rational(T::TARGET.0 as i128, 1_000_000_000);
+		let s = Fixed128::saturating_from_rational(normal_weight as i128, max_normal_weight as i128);
+		let diff = s.saturating_sub(target);
+
+		let first_term = T::VARIABILITY.saturating_mul(diff);
+		let second_term = Fixed128::from_parts(first_term.saturating_mul(first_term).deconstruct() / 2);
+		let adjustment = Fixed128::from_natural(1).saturating_add(first_term).saturating_add(second_term);
+
+		let next = FeeMultiplier(previous.0.saturating_mul(adjustment));
+		next.max(T::MIN_MULTIPLIER).min(T::MAX_MULTIPLIER)
 	}
 }
 
@@ -161,60 +656,173 @@ impl Default for FeeMultiplier {
 mod tests {
 	use super::*;
 
-	fn p(percent: u32) -> Perbill {
-		Perbill::from_parts(percent * 1_000_000_0)
+	#[test]
+	fn transaction_weight_defaults_to_normal_class() {
+		assert_eq!(TransactionWeight::default().classify(), DispatchClass::Normal);
+	}
+
+	#[test]
+	fn identity_fee_prices_weight_and_length_independently() {
+		assert_eq!(IdentityFee::<u64>::weight_to_fee(&7), 7);
+		assert_eq!(IdentityFee::<u64>::length_to_fee(7), 7);
+	}
+
+	#[test]
+	fn compute_transaction_fee_sums_length_and_weight_fees() {
+		let surge = FeeMultiplier::from_fixed128(Fixed128::from_natural(2));
+		// length_to_fee(3) + (weight_to_fee(100) * 2) == 3 + 200 == 203, with the multiplier only
+		// applied to the weight portion.
+		assert_eq!(compute_transaction_fee::<IdentityFee<u64>>(3, 100, surge, DispatchClass::Normal), 203);
+	}
+
+	#[test]
+	fn block_weight_respects_per_class_limits() {
+		let mut block_weight = BlockWeight::default();
+
+		assert!(block_weight.add(BlockWeight::normal_limit(), DispatchClass::Normal));
+		assert!(!block_weight.add(1, DispatchClass::Normal));
+		assert!(block_weight.add(BlockWeight::operational_limit(), DispatchClass::Operational));
+		assert!(!block_weight.add(1, DispatchClass::Operational));
+		// Mandatory dispatches always succeed and are never recorded.
+		assert!(block_weight.add(MAX_TRANSACTIONS_WEIGHT, DispatchClass::Mandatory));
+		assert_eq!(block_weight.total(), BlockWeight::normal_limit() + BlockWeight::operational_limit());
+	}
+
+	#[test]
+	fn fixed128_basic_arithmetic() {
+		assert_eq!(Fixed128::from_natural(1).saturating_add(Fixed128::from_natural(1)), Fixed128::from_natural(2));
+		assert_eq!(Fixed128::from_natural(3).saturating_sub(Fixed128::from_natural(1)), Fixed128::from_natural(2));
+		assert_eq!(Fixed128::from_natural(3).saturating_mul(Fixed128::from_natural(2)), Fixed128::from_natural(6));
+		assert_eq!(Fixed128::saturating_from_rational(1, 2).saturating_mul_int(10), 5);
+	}
+
+	#[test]
+	fn fixed128_checked_mul_does_not_overflow_below_its_true_range() {
+		// `100 * 100 == 10_000`, comfortably representable, but the raw, `DIV`-scaled product of
+		// the two operands overflows an `i128` long before the true result does; a naive
+		// `self.0.checked_mul(rhs.0)` would spuriously report this as `None`.
+		let hundred = Fixed128::from_natural(100);
+		assert_eq!(hundred.checked_mul(hundred), Some(Fixed128::from_natural(10_000)));
+	}
+
+	#[test]
+	fn fee_multiplier_default_is_identity() {
+		assert_eq!(FeeMultiplier::default().apply_to(1234), 1234);
 	}
 
 	#[test]
 	fn fee_multiplier_can_sum() {
-		assert_eq!(
-			FeeMultiplier::Positive(p(10), 1).sum(FeeMultiplier::Positive(p(10), 1)),
-			FeeMultiplier::Positive(p(20), 2)
-		);
+		let half = FeeMultiplier::from_fixed128(Fixed128::saturating_from_rational(1, 2));
+		let one = FeeMultiplier::default();
+		assert_eq!(half.sum(half), one);
+	}
 
-		assert_eq!(
-			FeeMultiplier::Positive(p(60), 0).sum(FeeMultiplier::Positive(p(60), 1)),
-			FeeMultiplier::Positive(p(20), 2)
-		);
+	#[test]
+	fn fee_multiplier_apply_to_class_exempts_operational_and_mandatory() {
+		let surge = FeeMultiplier::from_fixed128(Fixed128::from_natural(2));
+		assert_eq!(surge.apply_to_class(100, DispatchClass::Normal), 200);
+		assert_eq!(surge.apply_to_class(100, DispatchClass::Operational), 100);
+		assert_eq!(surge.apply_to_class(100, DispatchClass::Mandatory), 0);
+	}
 
-		assert_eq!(
-			FeeMultiplier::Positive(p(60), 0).sum(FeeMultiplier::Positive(p(60), 0)),
-			FeeMultiplier::Positive(p(20), 1)
-		);
+	#[test]
+	fn compute_fee_applies_weight_to_fee_after_the_multiplier() {
+		let surge = FeeMultiplier::from_fixed128(Fixed128::from_natural(2));
+		assert_eq!(compute_fee::<IdentityFee<u64>>(100, surge, DispatchClass::Normal), 200);
+		assert_eq!(compute_fee::<IdentityFee<u64>>(100, surge, DispatchClass::Operational), 100);
+		assert_eq!(compute_fee::<IdentityFee<u64>>(100, surge, DispatchClass::Mandatory), 0);
+	}
 
-		assert_eq!(
-			FeeMultiplier::Positive(p(10), 0).sum(FeeMultiplier::Positive(p(10), 1)),
-			FeeMultiplier::Positive(p(20), 1)
-		);
+	struct AdjustmentBounds;
+	impl TargetedFeeAdjustmentBounds for AdjustmentBounds {
+		// 25% target fullness.
+		const TARGET: Perbill = Perbill::from_percent(25);
+		const VARIABILITY: Fixed128 = Fixed128::from_parts(150_000_000_000_000_000); // 0.15
+		const MIN_MULTIPLIER: FeeMultiplier = FeeMultiplier::from_fixed128(Fixed128::from_parts(1_000_000_000_000)); // ~0
+		const MAX_MULTIPLIER: FeeMultiplier = FeeMultiplier::from_fixed128(Fixed128::from_parts(100_000_000_000_000_000_000_000));
+	}
 
-		assert_eq!(
-			FeeMultiplier::Positive(p(10), 0).sum(FeeMultiplier::Positive(p(10), 1)),
-			FeeMultiplier::Positive(p(20), 1)
-		);
+	type Adjustment = TargetedFeeAdjustment<AdjustmentBounds>;
 
-		assert_eq!(
-			FeeMultiplier::Positive(p(10), 0).sum(FeeMultiplier::Negative(p(10))),
-			FeeMultiplier::Positive(p(0), 0)
-		);
+	#[test]
+	fn default_fee_adjustment_targets_ideal_transactions_weight() {
+		assert_eq!(DefaultFeeAdjustment::TARGET, Perbill::from_percent(25));
 
-		// zero
-		assert_eq!(
-			FeeMultiplier::Positive(p(0), 0).sum(FeeMultiplier::Negative(p(10))),
-			FeeMultiplier::Negative(p(10))
-		);
-		assert_eq!(
-			FeeMultiplier::Negative(p(0)).sum(FeeMultiplier::Positive(p(10), 2)),
-			FeeMultiplier::Positive(p(10), 2)
+		let previous = FeeMultiplier::from_fixed128(Fixed128::from_natural(2));
+		let at_ideal_fullness = TargetedFeeAdjustment::<DefaultFeeAdjustment>::convert(
+			previous, IDEAL_TRANSACTIONS_WEIGHT, MAX_TRANSACTIONS_WEIGHT,
 		);
+		assert_eq!(at_ideal_fullness, previous);
+	}
 
-		// asymmetric operation.
-		assert_eq!(
-			FeeMultiplier::Positive(p(10), 0).sum(FeeMultiplier::Negative(p(30))),
-			FeeMultiplier::Negative(p(20))
-		);
-		assert_eq!(
-			FeeMultiplier::Negative(p(30)).sum(FeeMultiplier::Positive(p(10), 0)),
-			FeeMultiplier::Negative(p(20))
-		);
+	#[test]
+	fn fee_multiplier_unchanged_at_target_fullness() {
+		let previous = FeeMultiplier::from_fixed128(Fixed128::from_natural(2));
+		let next = Adjustment::convert(previous, 25, 100);
+		assert_eq!(next, previous);
+	}
+
+	#[test]
+	fn fee_multiplier_is_monotonic_in_fullness() {
+		let previous = FeeMultiplier::default();
+		let low = Adjustment::convert(previous, 10, 100);
+		let high = Adjustment::convert(previous, 90, 100);
+		assert!(low < previous);
+		assert!(high > previous);
+		assert!(low < high);
+	}
+
+	#[test]
+	fn long_run_of_empty_blocks_drives_multiplier_to_min() {
+		let mut multiplier = FeeMultiplier::default();
+		for i in 0..1_000 {
+			multiplier = Adjustment::convert(multiplier, 0, 100);
+			if i == 300 {
+				// Should still be shrinking gradually at this point, not already pinned to the
+				// floor -- otherwise the clamp at the end of this test would pass "by accident"
+				// even if the decay shot straight to `MIN_MULTIPLIER` far too early.
+				assert!(multiplier > AdjustmentBounds::MIN_MULTIPLIER);
+			}
+		}
+		assert_eq!(multiplier, AdjustmentBounds::MIN_MULTIPLIER);
+	}
+
+	#[test]
+	fn long_run_of_full_blocks_grows_multiplier_to_max() {
+		let mut multiplier = FeeMultiplier::default();
+		for i in 0..1_000 {
+			multiplier = Adjustment::convert(multiplier, 100, 100);
+			if i == 59 {
+				// Should still be growing gradually at this point, not already pinned to the
+				// ceiling -- otherwise the clamp at the end of this test would pass "by accident"
+				// even if the growth shot straight to `MAX_MULTIPLIER` far too early.
+				assert!(multiplier < AdjustmentBounds::MAX_MULTIPLIER);
+			}
+		}
+		assert_eq!(multiplier, AdjustmentBounds::MAX_MULTIPLIER);
+	}
+
+	#[test]
+	fn full_blocks_grow_multiplier_geometrically() {
+		// `s` is held fixed at `100%` every block, so the per-block update factor is constant and
+		// the multiplier should grow as a clean geometric series: doubling the number of elapsed
+		// blocks should square the accumulated growth (since `FeeMultiplier::default()` is `1.0`).
+		let mut multiplier = FeeMultiplier::default();
+		for _ in 0..30 {
+			multiplier = Adjustment::convert(multiplier, 100, 100);
+		}
+		let after_30 = multiplier.into_fixed128();
+		for _ in 0..30 {
+			multiplier = Adjustment::convert(multiplier, 100, 100);
+		}
+		let after_60 = multiplier.into_fixed128();
+
+		let expected_after_60 = after_30.checked_mul(after_30).expect("growth over 60 blocks must not overflow Fixed128");
+		let diff = if after_60 > expected_after_60 {
+			after_60.checked_sub(expected_after_60).unwrap()
+		} else {
+			expected_after_60.checked_sub(after_60).unwrap()
+		};
+		assert!(diff < Fixed128::saturating_from_rational(1, 1_000), "growth was not geometric: {:?} vs {:?}", after_60, expected_after_60);
 	}
 }
\ No newline at end of file